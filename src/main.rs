@@ -2,7 +2,11 @@
 extern crate rustwlc;
 
 use std::sync::RwLock;
+use std::sync::mpsc::{channel, Sender};
 use std::cmp;
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Write};
+use std::os::unix::net::{UnixListener, UnixStream};
 use std::process::Command;
 
 use rustwlc::*;
@@ -13,11 +17,245 @@ struct Compositor {
 	pub edges: ResizeEdge,
 }
 
+#[derive(Clone, Copy, PartialEq)]
+enum Layout {
+	Tiled,
+	Monocle,
+	Grid,
+	Spiral,
+}
+
+impl Layout {
+	fn next(self) -> Layout {
+		match self {
+			Layout::Tiled => Layout::Monocle,
+			Layout::Monocle => Layout::Grid,
+			Layout::Grid => Layout::Spiral,
+			Layout::Spiral => Layout::Tiled,
+		}
+	}
+}
+
 lazy_static! {
 	static ref COMPOSITOR: RwLock<Compositor> =
 		RwLock::new(Compositor { view: None, edges: ResizeEdge::empty() });
 	static ref HIDDEN: RwLock<Vec<WlcView>> =
 		RwLock::new(Vec::new());
+	static ref LAYOUTS: RwLock<HashMap<WlcOutput, Layout>> =
+		RwLock::new(HashMap::new());
+	static ref BINDINGS: RwLock<Vec<Binding>> =
+		RwLock::new(Vec::new());
+	static ref WORKSPACES: RwLock<HashMap<WlcOutput, u32>> =
+		RwLock::new(HashMap::new());
+	static ref VIEW_WORKSPACE: RwLock<HashMap<WlcView, u32>> =
+		RwLock::new(HashMap::new());
+	static ref FOCUSED: RwLock<Option<WlcView>> =
+		RwLock::new(None);
+	static ref IPC_QUEUE: RwLock<Vec<IpcRequest>> =
+		RwLock::new(Vec::new());
+	static ref OUTPUTS: RwLock<Vec<WlcOutput>> =
+		RwLock::new(Vec::new());
+	static ref SCRATCHPAD: RwLock<HashMap<u32, WlcView>> =
+		RwLock::new(HashMap::new());
+	static ref SCRATCHPAD_REVEALED: RwLock<HashMap<u32, WlcView>> =
+		RwLock::new(HashMap::new());
+	static ref SCRATCHED: RwLock<Vec<WlcView>> =
+		RwLock::new(Vec::new());
+}
+
+const SCRATCHPAD_SLOT: u32 = 0;
+
+fn live_outputs() -> Vec<WlcOutput> {
+	OUTPUTS.read().unwrap().clone()
+}
+
+struct IpcRequest {
+	command: String,
+	reply: Sender<String>,
+}
+
+const WORKSPACE_COUNT: u32 = 9;
+
+fn is_valid_workspace(workspace: u32) -> bool {
+	workspace >= 1 && workspace <= WORKSPACE_COUNT
+}
+
+fn workspace_mask(workspace: u32) -> u32 {
+	1 << (workspace - 1)
+}
+
+fn get_workspace(output: WlcOutput) -> u32 {
+	*WORKSPACES.read().unwrap().get(&output).unwrap_or(&1)
+}
+
+fn get_view_workspace(view: WlcView) -> u32 {
+	*VIEW_WORKSPACE.read().unwrap().get(&view).unwrap_or(&1)
+}
+
+fn switch_workspace(output: WlcOutput, workspace: u32) {
+	WORKSPACES.write().unwrap().insert(output, workspace);
+	output.set_mask(workspace_mask(workspace));
+	for view in output.get_views() {
+		let vw = get_view_workspace(view);
+		view.set_mask(workspace_mask(vw));
+	}
+	update_layout(output);
+}
+
+fn move_to_workspace(view: WlcView, workspace: u32) {
+	if view.is_root() { return }
+	VIEW_WORKSPACE.write().unwrap().insert(view, workspace);
+	view.set_mask(workspace_mask(workspace));
+	update_layout(view.get_output());
+}
+
+fn send_to_next_output(view: WlcView) {
+	if view.is_root() { return }
+	let outputs = live_outputs();
+	if outputs.len() < 2 { return }
+	let source = view.get_output();
+	let idx = outputs.iter().position(|&o| o == source).unwrap_or(0);
+	let dest = outputs[(idx + 1) % outputs.len()];
+	view.set_output(dest);
+	view.set_mask(workspace_mask(get_view_workspace(view)));
+	update_layout(source);
+	update_layout(dest);
+}
+
+#[derive(Clone)]
+enum Action {
+	Close,
+	FocusLeft,
+	FocusRight,
+	Hide,
+	Unhide,
+	LayoutNext,
+	Quit,
+	Spawn(Vec<String>),
+	Workspace(u32),
+	MoveToWorkspace(u32),
+	NextOutput,
+	ScratchpadToggle,
+}
+
+struct Binding {
+	mods: Mod,
+	sym: u32,
+	action: Action,
+}
+
+fn keysym_from_name(name: &str) -> Option<u32> {
+	match name {
+		"Left" => Some(keysyms::KEY_Left),
+		"Right" => Some(keysyms::KEY_Right),
+		"Up" => Some(keysyms::KEY_Up),
+		"Down" => Some(keysyms::KEY_Down),
+		"space" => Some(keysyms::KEY_space),
+		"minus" => Some(keysyms::KEY_minus),
+		_ => {
+			let mut chars = name.chars();
+			match (chars.next(), chars.next()) {
+				(Some(c), None) if c.is_ascii() => Some(c as u32),
+				_ => None,
+			}
+		}
+	}
+}
+
+fn mod_from_name(name: &str) -> Mod {
+	match name {
+		"Alt" => MOD_ALT,
+		"Shift" => MOD_SHIFT,
+		"Ctrl" => MOD_CTRL,
+		"Logo" => MOD_LOGO,
+		_ => Mod::empty(),
+	}
+}
+
+fn parse_chord(chord: &str) -> Option<(Mod, u32)> {
+	let mut parts: Vec<&str> = chord.split('+').collect();
+	let key = parts.pop()?;
+	let sym = keysym_from_name(key)?;
+	let mods = parts.into_iter().fold(Mod::empty(), |acc, part| acc | mod_from_name(part));
+	Some((mods, sym))
+}
+
+fn action_from_tokens(tokens: &[&str]) -> Option<Action> {
+	match tokens.first() {
+		Some(&"close") => Some(Action::Close),
+		Some(&"focus-left") => Some(Action::FocusLeft),
+		Some(&"focus-right") => Some(Action::FocusRight),
+		Some(&"hide") => Some(Action::Hide),
+		Some(&"unhide") => Some(Action::Unhide),
+		Some(&"layout-next") => Some(Action::LayoutNext),
+		Some(&"quit") => Some(Action::Quit),
+		Some(&"spawn") if tokens.len() > 1 =>
+			Some(Action::Spawn(tokens[1..].iter().map(|s| s.to_string()).collect())),
+		Some(&"workspace") if tokens.len() == 2 =>
+			tokens[1].parse().ok().filter(|&w| is_valid_workspace(w)).map(Action::Workspace),
+		Some(&"move-to-workspace") if tokens.len() == 2 =>
+			tokens[1].parse().ok().filter(|&w| is_valid_workspace(w)).map(Action::MoveToWorkspace),
+		Some(&"next-output") => Some(Action::NextOutput),
+		Some(&"scratchpad-toggle") => Some(Action::ScratchpadToggle),
+		_ => None,
+	}
+}
+
+fn parse_config(contents: &str) -> Vec<Binding> {
+	contents.lines()
+		.map(|line| line.trim())
+		.filter(|line| !line.is_empty() && !line.starts_with('#'))
+		.filter_map(|line| {
+			let mut tokens = line.split_whitespace();
+			let (mods, sym) = parse_chord(tokens.next()?)?;
+			let rest: Vec<&str> = tokens.collect();
+			let action = action_from_tokens(&rest)?;
+			Some(Binding { mods: mods, sym: sym, action: action })
+		})
+		.collect()
+}
+
+fn default_bindings() -> Vec<Binding> {
+	let mut bindings = vec![
+		Binding { mods: MOD_ALT, sym: keysyms::KEY_d, action: Action::Close },
+		Binding { mods: MOD_ALT, sym: keysyms::KEY_Left, action: Action::FocusLeft },
+		Binding { mods: MOD_ALT, sym: keysyms::KEY_Right, action: Action::FocusRight },
+		Binding { mods: MOD_ALT, sym: keysyms::KEY_Down, action: Action::Hide },
+		Binding { mods: MOD_ALT, sym: keysyms::KEY_Up, action: Action::Unhide },
+		Binding { mods: MOD_ALT, sym: keysyms::KEY_space, action: Action::LayoutNext },
+		Binding { mods: MOD_ALT, sym: keysyms::KEY_o, action: Action::Quit },
+		Binding { mods: MOD_ALT, sym: keysyms::KEY_q, action: Action::Spawn(vec!["/usr/local/bin/wayst".to_string()]) },
+	];
+	for workspace in 1..10u32 {
+		let sym = '0' as u32 + workspace;
+		bindings.push(Binding { mods: MOD_ALT, sym: sym, action: Action::Workspace(workspace) });
+		bindings.push(Binding { mods: MOD_ALT | MOD_SHIFT, sym: sym, action: Action::MoveToWorkspace(workspace) });
+	}
+	bindings.push(Binding { mods: MOD_ALT, sym: keysyms::KEY_n, action: Action::NextOutput });
+	bindings.push(Binding { mods: MOD_ALT, sym: keysyms::KEY_minus, action: Action::ScratchpadToggle });
+	bindings
+}
+
+fn config_path() -> Option<String> {
+	std::env::var("HOME").ok().map(|home| format!("{}/.config/noway/config", home))
+}
+
+fn load_bindings() -> Vec<Binding> {
+	config_path()
+		.and_then(|path| std::fs::read_to_string(path).ok())
+		.map(|contents| parse_config(&contents))
+		.filter(|bindings| !bindings.is_empty())
+		.unwrap_or_else(default_bindings)
+}
+
+fn get_layout(output: WlcOutput) -> Layout {
+	*LAYOUTS.read().unwrap().get(&output).unwrap_or(&Layout::Tiled)
+}
+
+fn cycle_layout(output: WlcOutput) {
+	let mut layouts = LAYOUTS.write().unwrap();
+	let next = layouts.get(&output).unwrap_or(&Layout::Tiled).next();
+	layouts.insert(output, next);
 }
 
 fn start_interactive_action(view: WlcView) -> bool {
@@ -35,10 +273,16 @@ fn start_interactive_move(view: WlcView) {
 	start_interactive_action(view);
 }
 
-fn start_interactive_resize(view: WlcView) {
+fn start_interactive_resize(view: WlcView, origin: &Point) {
 	if start_interactive_action(view) {
+		let geo = view.get_geometry().unwrap();
+		let halfw = geo.origin.x + geo.size.w as i32 / 2;
+		let halfh = geo.origin.y + geo.size.h as i32 / 2;
+		let mut edges = ResizeEdge::empty();
+		if origin.x < halfw { edges |= RESIZE_LEFT } else if origin.x > halfw { edges |= RESIZE_RIGHT }
+		if origin.y < halfh { edges |= RESIZE_TOP } else if origin.y > halfh { edges |= RESIZE_BOTTOM }
 		let mut comp = COMPOSITOR.write().unwrap();
-		comp.edges = RESIZE_RIGHT | RESIZE_BOTTOM;
+		comp.edges = edges;
 		view.set_state(VIEW_RESIZING, true);
 	}
 }
@@ -54,12 +298,8 @@ fn stop_interactive_action() {
 	comp.edges = ResizeEdge::empty();
 }
 
-fn update_layout(output: WlcOutput) {
-	let resolution = output.get_resolution().unwrap();
-	let views = output.get_views();
-	if views.is_empty() { return }
-	let hidden = HIDDEN.read().unwrap();
-	let viewlen = views.len() - hidden.len();
+fn layout_tiled(resolution: Size, views: &[WlcView]) {
+	let viewlen = views.len();
 	if viewlen == 1 {
 		views[0].set_geometry(ResizeEdge::empty(), Geometry {
 			origin: Point { x: 0, y: 0 },
@@ -69,7 +309,7 @@ fn update_layout(output: WlcOutput) {
 		let w = resolution.w / 2;
 		let h0 = resolution.h / ((viewlen + 1) / 2) as u32;
 		let h1 = resolution.h / (viewlen / 2) as u32;
-		for (i, view) in views.iter().filter(|v| !hidden.contains(v)).enumerate() {
+		for (i, view) in views.iter().enumerate() {
 			let (x, h) = if i&1 == 1 { (w as i32, h1) } else { (0, h0) };
 			view.set_geometry(ResizeEdge::empty(), Geometry {
 				origin: Point {
@@ -82,15 +322,109 @@ fn update_layout(output: WlcOutput) {
 	}
 }
 
+fn layout_monocle(resolution: Size, views: &[WlcView]) {
+	for view in views {
+		view.set_geometry(ResizeEdge::empty(), Geometry {
+			origin: Point { x: 0, y: 0 },
+			size: resolution,
+		});
+	}
+	if let Some(top) = views.last() {
+		top.bring_to_front();
+	}
+}
+
+fn layout_grid(resolution: Size, views: &[WlcView]) {
+	let n = views.len();
+	let cols = (n as f64).sqrt().ceil() as usize;
+	let rows = (n + cols - 1) / cols;
+	let h = resolution.h / rows as u32;
+	for (i, view) in views.iter().enumerate() {
+		let row = i / cols;
+		let col = i % cols;
+		let cols_in_row = cmp::min(cols, n - row * cols);
+		let w = resolution.w / cols_in_row as u32;
+		let row_h = if row == rows - 1 { resolution.h - h * (rows as u32 - 1) } else { h };
+		view.set_geometry(ResizeEdge::empty(), Geometry {
+			origin: Point { x: (col as u32 * w) as i32, y: (row as u32 * h) as i32 },
+			size: Size { w: w, h: row_h },
+		});
+	}
+}
+
+fn layout_spiral(resolution: Size, views: &[WlcView]) {
+	let n = views.len();
+	let mut origin = Point { x: 0, y: 0 };
+	let mut size = resolution;
+	let mut split_width = true;
+	for (i, view) in views.iter().enumerate() {
+		if i == n - 1 {
+			view.set_geometry(ResizeEdge::empty(), Geometry { origin: origin, size: size });
+			break;
+		}
+		if split_width {
+			let w = size.w / 2;
+			view.set_geometry(ResizeEdge::empty(), Geometry { origin: origin, size: Size { w: w, h: size.h } });
+			origin = Point { x: origin.x + w as i32, y: origin.y };
+			size = Size { w: size.w - w, h: size.h };
+		} else {
+			let h = size.h / 2;
+			view.set_geometry(ResizeEdge::empty(), Geometry { origin: origin, size: Size { w: size.w, h: h } });
+			origin = Point { x: origin.x, y: origin.y + h as i32 };
+			size = Size { w: size.w, h: size.h - h };
+		}
+		split_width = !split_width;
+	}
+}
+
+fn update_layout(output: WlcOutput) {
+	let resolution = output.get_resolution().unwrap();
+	let views = output.get_views();
+	if views.is_empty() { return }
+	let hidden = HIDDEN.read().unwrap();
+	let scratched = SCRATCHED.read().unwrap();
+	let active = get_workspace(output);
+	let visible: Vec<WlcView> = views.into_iter()
+		.filter(|v| !hidden.contains(v) && !scratched.contains(v) && get_view_workspace(*v) == active)
+		.collect();
+	if visible.is_empty() { return }
+	match get_layout(output) {
+		Layout::Tiled => layout_tiled(resolution, &visible),
+		Layout::Monocle => layout_monocle(resolution, &visible),
+		Layout::Grid => layout_grid(resolution, &visible),
+		Layout::Spiral => layout_spiral(resolution, &visible),
+	}
+}
+
 extern fn on_output_resolution(output: WlcOutput, _from: &Size, _to: &Size) {
 	update_layout(output);
 }
 
+extern fn on_output_created(output: WlcOutput) -> bool {
+	OUTPUTS.write().unwrap().push(output);
+	true
+}
+
+extern fn on_output_destroyed(output: WlcOutput) {
+	OUTPUTS.write().unwrap().retain(|&o| o != output);
+	if let Some(dest) = live_outputs().into_iter().next() {
+		let workspace = get_workspace(dest);
+		for view in output.get_views() {
+			view.set_output(dest);
+			VIEW_WORKSPACE.write().unwrap().insert(view, workspace);
+		}
+		switch_workspace(dest, workspace);
+	}
+}
+
 extern fn on_view_created(view: WlcView) -> bool {
-	view.set_mask(view.get_output().get_mask());
+	let output = view.get_output();
+	let workspace = get_workspace(output);
+	VIEW_WORKSPACE.write().unwrap().insert(view, workspace);
+	view.set_mask(workspace_mask(workspace));
 	view.bring_to_front();
 	view.focus();
-	update_layout(view.get_output());
+	update_layout(output);
 	true
 }
 
@@ -105,100 +439,178 @@ extern fn on_view_destroyed(view: WlcView) {
 			hidden.remove(idx);
 		}
 	}
+	VIEW_WORKSPACE.write().unwrap().remove(&view);
+	{
+		let mut scratched = SCRATCHED.write().unwrap();
+		if let Some(idx) = scratched.iter().position(|&v| v == view) {
+			scratched.remove(idx);
+		}
+	}
+	SCRATCHPAD.write().unwrap().retain(|_, &mut v| v != view);
+	SCRATCHPAD_REVEALED.write().unwrap().retain(|_, &mut v| v != view);
+	if *FOCUSED.read().unwrap() == Some(view) {
+		*FOCUSED.write().unwrap() = None;
+	}
 	update_layout(view.get_output());
 }
 
 extern fn on_view_focus(view: WlcView, focused: bool) {
 	view.set_state(VIEW_ACTIVATED, focused);
+	if focused {
+		*FOCUSED.write().unwrap() = Some(view);
+	}
 }
 
 extern fn on_view_request_move(view: WlcView, _: &Point) {
 	start_interactive_move(view);
 }
 
-extern fn on_view_request_resize(view: WlcView, _: ResizeEdge, _: &Point) {
-	start_interactive_resize(view);
+extern fn on_view_request_resize(view: WlcView, _: ResizeEdge, origin: &Point) {
+	start_interactive_resize(view, origin);
+}
+
+fn focus_left(view: WlcView) {
+	if !view.is_root() {
+		view.send_to_back();
+		let output = view.get_output();
+		let mut views = output.get_views();
+		{
+			let hidden = HIDDEN.read().unwrap();
+			let active = get_workspace(output);
+			views.retain(|v| !hidden.contains(v) && get_view_workspace(*v) == active);
+		}
+		if views.len() < 2 { return }
+		views.last().unwrap().focus();
+	}
+}
+
+fn focus_right(view: WlcView) {
+	if !view.is_root() {
+		let output = view.get_output();
+		let mut views = output.get_views();
+		{
+			let hidden = HIDDEN.read().unwrap();
+			let active = get_workspace(output);
+			views.retain(|v| !hidden.contains(v) && get_view_workspace(*v) == active);
+		}
+		if views.len() < 2 { return }
+		let first = views.first().unwrap();
+		first.bring_to_front();
+		first.focus();
+	}
+}
+
+fn hide_view(view: WlcView) {
+	if !view.is_root() {
+		view.send_to_back();
+		{
+			let mut hidden = HIDDEN.write().unwrap();
+			hidden.push(view);
+		}
+		update_layout(view.get_output());
+	}
+}
+
+fn unhide_view(view: WlcView) {
+	if !view.is_root() {
+		let output = view.get_output();
+		let active = get_workspace(output);
+		let hview = {
+			let mut hidden = HIDDEN.write().unwrap();
+			match hidden.pop() {
+				Some(hview) if get_view_workspace(hview) == active => Some(hview),
+				Some(hview) => { hidden.push(hview); None }
+				None => None,
+			}
+		};
+		if let Some(hview) = hview {
+			hview.bring_to_front();
+			hview.focus();
+			update_layout(output);
+		}
+	}
+}
+
+fn scratchpad_toggle(view: WlcView) {
+	let revealed = SCRATCHPAD_REVEALED.write().unwrap().remove(&SCRATCHPAD_SLOT);
+	if let Some(revealed) = revealed {
+		SCRATCHPAD.write().unwrap().insert(SCRATCHPAD_SLOT, revealed);
+		revealed.send_to_back();
+		update_layout(revealed.get_output());
+		return
+	}
+	let parked = SCRATCHPAD.write().unwrap().remove(&SCRATCHPAD_SLOT);
+	if let Some(parked) = parked {
+		let output = parked.get_output();
+		let workspace = get_workspace(output);
+		VIEW_WORKSPACE.write().unwrap().insert(parked, workspace);
+		parked.set_mask(workspace_mask(workspace));
+		let resolution = output.get_resolution().unwrap();
+		let size = Size { w: resolution.w * 3 / 5, h: resolution.h * 3 / 5 };
+		let origin = Point {
+			x: (resolution.w as i32 - size.w as i32) / 2,
+			y: (resolution.h as i32 - size.h as i32) / 2,
+		};
+		parked.set_geometry(ResizeEdge::empty(), Geometry { origin: origin, size: size });
+		parked.bring_to_front();
+		parked.focus();
+		SCRATCHPAD_REVEALED.write().unwrap().insert(SCRATCHPAD_SLOT, parked);
+	} else if !view.is_root() {
+		SCRATCHED.write().unwrap().push(view);
+		SCRATCHPAD.write().unwrap().insert(SCRATCHPAD_SLOT, view);
+		view.send_to_back();
+		update_layout(view.get_output());
+	}
 }
 
 extern fn on_keyboard_key(view: WlcView, _time: u32, mods: &KeyboardModifiers, key: u32, state: KeyState) -> bool {
+	if state != KeyState::Pressed { return false }
 	let sym = input::keyboard::get_keysym_for_key(key, *mods);
-	if state == KeyState::Pressed && mods.mods == MOD_ALT {
-		match sym {
-			keysyms::KEY_d => {
-				if !view.is_root() {
-					view.close();
-				}
-			}
-			keysyms::KEY_Left => {
-				if !view.is_root() {
-					view.send_to_back();
-					let mut views = view.get_output().get_views();
-					{
-						let hidden = HIDDEN.read().unwrap();
-						views.retain(|v| !hidden.contains(v));
-					}
-					if views.len() < 2 { return true }
-					views.last().unwrap().focus();
-				}
-			}
-			keysyms::KEY_Right => {
-				if !view.is_root() {
-					let mut views = view.get_output().get_views();
-					{
-						let hidden = HIDDEN.read().unwrap();
-						views.retain(|v| !hidden.contains(v));
-					}
-					if views.len() < 2 { return true }
-					let first = views.first().unwrap();
-					first.bring_to_front();
-					first.focus();
-				}
+	let action = {
+		let bindings = BINDINGS.read().unwrap();
+		bindings.iter().find(|b| b.mods == mods.mods && b.sym == sym).map(|b| b.action.clone())
+	};
+	match action {
+		Some(Action::Close) => {
+			if !view.is_root() {
+				view.close();
 			}
-			keysyms::KEY_Down => {
-				if !view.is_root() {
-					view.send_to_back();
-					{
-						let mut hidden = HIDDEN.write().unwrap();
-						hidden.push(view);
-					}
-					update_layout(view.get_output());
-				}
-			}
-			keysyms::KEY_Up => {
-				if !view.is_root() {
-					if let Some(hview) = {
-						let mut hidden = HIDDEN.write().unwrap();
-						hidden.pop()
-					} {
-						hview.bring_to_front();
-						hview.focus();
-						update_layout(view.get_output());
-					}
+		}
+		Some(Action::FocusLeft) => focus_left(view),
+		Some(Action::FocusRight) => focus_right(view),
+		Some(Action::Hide) => hide_view(view),
+		Some(Action::Unhide) => unhide_view(view),
+		Some(Action::LayoutNext) => {
+			let output = view.get_output();
+			cycle_layout(output);
+			update_layout(output);
+		}
+		Some(Action::Quit) => terminate(),
+		Some(Action::Spawn(argv)) => {
+			if let Some((cmd, args)) = argv.split_first() {
+				if let Err(err) = Command::new(cmd).args(args).spawn() {
+					eprintln!("noway: failed to spawn {}: {}", cmd, err);
 				}
 			}
-			keysyms::KEY_o => {
-				terminate();
-			}
-			keysyms::KEY_q => {
-				Command::new("/usr/local/bin/wayst").spawn().expect("Error executing terminal");
-			}
-			_ => return false
 		}
-		true
-	} else {
-		false
+		Some(Action::Workspace(workspace)) => switch_workspace(view.get_output(), workspace),
+		Some(Action::MoveToWorkspace(workspace)) => move_to_workspace(view, workspace),
+		Some(Action::NextOutput) => send_to_next_output(view),
+		Some(Action::ScratchpadToggle) => scratchpad_toggle(view),
+		None => return false,
 	}
+	true
 }
 
 extern fn on_pointer_button(view: WlcView, _time: u32, mods: &KeyboardModifiers,
-							button: u32, state: ButtonState, _: &Point) -> bool {
+							button: u32, state: ButtonState, point: &Point) -> bool {
 	if state == ButtonState::Pressed {
 		if !view.is_root() && mods.mods.contains(MOD_ALT) {
 			view.focus();
 			if mods.mods.contains(MOD_ALT) {
 				match button {
 					0x110 => start_interactive_move(view),
-					0x111 => start_interactive_resize(view),
+					0x111 => start_interactive_resize(view, point),
 					_ => (),
 				}
 			}
@@ -216,8 +628,20 @@ extern fn on_pointer_motion(_in_view: WlcView, _time: u32, point: &Point) -> boo
 	if let Some(ref view) = comp.view {
 		let mut geo = view.get_geometry().unwrap();
 		if comp.edges.bits() != 0 {
-			geo.size.w = if point.x > geo.origin.x { cmp::max(point.x - geo.origin.x, 32) as u32 } else { 32 };
-			geo.size.h = if point.y > geo.origin.y { cmp::max(point.y - geo.origin.y, 32) as u32 } else { 32 };
+			if comp.edges.contains(RESIZE_LEFT) {
+				let right = geo.origin.x + geo.size.w as i32;
+				geo.origin.x = cmp::min(point.x, right - 32);
+				geo.size.w = (right - geo.origin.x) as u32;
+			} else if comp.edges.contains(RESIZE_RIGHT) {
+				geo.size.w = if point.x > geo.origin.x { cmp::max(point.x - geo.origin.x, 32) as u32 } else { 32 };
+			}
+			if comp.edges.contains(RESIZE_TOP) {
+				let bottom = geo.origin.y + geo.size.h as i32;
+				geo.origin.y = cmp::min(point.y, bottom - 32);
+				geo.size.h = (bottom - geo.origin.y) as u32;
+			} else if comp.edges.contains(RESIZE_BOTTOM) {
+				geo.size.h = if point.y > geo.origin.y { cmp::max(point.y - geo.origin.y, 32) as u32 } else { 32 };
+			}
 		}
 		else {
 			geo.origin = *point;
@@ -229,7 +653,137 @@ extern fn on_pointer_motion(_in_view: WlcView, _time: u32, point: &Point) -> boo
 	}
 }
 
+fn view_by_id(id: usize) -> Option<WlcView> {
+	live_outputs().iter().flat_map(|output| output.get_views()).nth(id)
+}
+
+fn set_active_layout(layout: Layout) {
+	for output in live_outputs() {
+		LAYOUTS.write().unwrap().insert(output, layout);
+		update_layout(output);
+	}
+}
+
+fn unhide_last() -> String {
+	let hview = { HIDDEN.write().unwrap().pop() };
+	match hview {
+		Some(view) => {
+			view.bring_to_front();
+			view.focus();
+			update_layout(view.get_output());
+			"ok".to_string()
+		}
+		None => "error: nothing hidden".to_string(),
+	}
+}
+
+fn handle_ipc_command(command: &str) -> String {
+	let mut tokens = command.split_whitespace();
+	match tokens.next() {
+		Some("list") => {
+			live_outputs().iter()
+				.flat_map(|output| output.get_views())
+				.enumerate()
+				.map(|(id, view)| {
+					let geo = view.get_geometry().unwrap_or(Geometry {
+						origin: Point { x: 0, y: 0 },
+						size: Size { w: 0, h: 0 },
+					});
+					format!("{}\t{}\t{},{} {}x{}\t{}", id, view.get_title(),
+						geo.origin.x, geo.origin.y, geo.size.w, geo.size.h,
+						get_view_workspace(view))
+				})
+				.collect::<Vec<String>>()
+				.join("\n")
+		}
+		Some("focus") => match tokens.next().and_then(|id| id.parse().ok()).and_then(view_by_id) {
+			Some(view) => { view.bring_to_front(); view.focus(); "ok".to_string() }
+			None => "error: no such view".to_string(),
+		},
+		Some("close") => match tokens.next().and_then(|id| id.parse().ok()).and_then(view_by_id) {
+			Some(view) => { view.close(); "ok".to_string() }
+			None => "error: no such view".to_string(),
+		},
+		Some("layout") => match tokens.next() {
+			Some("tiled") => { set_active_layout(Layout::Tiled); "ok".to_string() }
+			Some("monocle") => { set_active_layout(Layout::Monocle); "ok".to_string() }
+			Some("grid") => { set_active_layout(Layout::Grid); "ok".to_string() }
+			Some("spiral") => { set_active_layout(Layout::Spiral); "ok".to_string() }
+			_ => "error: unknown layout".to_string(),
+		},
+		Some("workspace") => match tokens.next().and_then(|n| n.parse().ok()).filter(|&w| is_valid_workspace(w)) {
+			Some(workspace) => {
+				for output in live_outputs() {
+					switch_workspace(output, workspace);
+				}
+				"ok".to_string()
+			}
+			None => "error: bad workspace".to_string(),
+		},
+		Some("hide") => match *FOCUSED.read().unwrap() {
+			Some(view) => { hide_view(view); "ok".to_string() }
+			None => "error: no focused view".to_string(),
+		},
+		Some("unhide") => unhide_last(),
+		_ => "error: unknown command".to_string(),
+	}
+}
+
+fn drain_ipc_queue() {
+	let requests: Vec<IpcRequest> = {
+		let mut queue = IPC_QUEUE.write().unwrap();
+		std::mem::replace(&mut *queue, Vec::new())
+	};
+	for request in requests {
+		let response = handle_ipc_command(&request.command);
+		let _ = request.reply.send(response);
+	}
+}
+
+extern fn on_ipc_tick() {
+	drain_ipc_queue();
+}
+
+fn handle_ipc_connection(stream: UnixStream) {
+	let mut reader = BufReader::new(match stream.try_clone() {
+		Ok(stream) => stream,
+		Err(_) => return,
+	});
+	let mut line = String::new();
+	if reader.read_line(&mut line).unwrap_or(0) == 0 { return }
+	let (tx, rx) = channel();
+	IPC_QUEUE.write().unwrap().push(IpcRequest { command: line.trim().to_string(), reply: tx });
+	if let Ok(response) = rx.recv() {
+		let mut stream = stream;
+		let _ = writeln!(stream, "{}", response);
+	}
+}
+
+fn spawn_ipc_thread() {
+	let sock_path = match std::env::var("NOWAY_SOCK") {
+		Ok(path) => path,
+		Err(_) => return,
+	};
+	std::thread::spawn(move || {
+		let _ = std::fs::remove_file(&sock_path);
+		let listener = match UnixListener::bind(&sock_path) {
+			Ok(listener) => listener,
+			Err(_) => return,
+		};
+		for stream in listener.incoming() {
+			if let Ok(stream) = stream {
+				handle_ipc_connection(stream);
+			}
+		}
+	});
+}
+
 fn main() {
+	*BINDINGS.write().unwrap() = load_bindings();
+	spawn_ipc_thread();
+	callback::timer(on_ipc_tick);
+	callback::output_created(on_output_created);
+	callback::output_destroyed(on_output_destroyed);
 	callback::output_resolution(on_output_resolution);
 	callback::view_created(on_view_created);
 	callback::view_destroyed(on_view_destroyed);